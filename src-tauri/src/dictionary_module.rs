@@ -32,8 +32,8 @@ Althasol = 阿爾瑟索
  */
 
 use std::fs::File;
-use std::io::{BufRead, BufReader, ErrorKind, Write};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
 
 use std::error::Error;
 use std::fmt::{self, Display, Formatter};
@@ -41,6 +41,10 @@ use std::io;
 
 use std::collections::HashMap;
 
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
 #[derive(Debug)]
 pub enum BrokenReason {
     BadLeftString,
@@ -61,6 +65,7 @@ pub enum ReadError {
         left_string: String,
         reason: BrokenReason,
     },
+    BinaryCorrupt(String),
 }
 
 impl From<io::Error> for ReadError {
@@ -120,6 +125,9 @@ impl Display for ReadError {
                     }
                 }
             }
+            ReadError::BinaryCorrupt(reason) => {
+                f.write_fmt(format_args!("the binary dictionary file is corrupt: {}", reason))
+            }
         }
     }
 }
@@ -131,8 +139,10 @@ pub enum WriteError {
     IOError(io::Error),
     BadLeftString,
     BadRightString,
+    BadAttrString,
     Duplicated,
     Same,
+    AttrsNotSupported,
 }
 
 impl From<io::Error> for WriteError {
@@ -149,18 +159,197 @@ impl Display for WriteError {
             WriteError::IOError(err) => Display::fmt(&err, f),
             WriteError::BadLeftString => f.write_str("the left word is not correct"),
             WriteError::BadRightString => f.write_str("the right word is not correct"),
+            WriteError::BadAttrString => {
+                f.write_str("an attribute key or value is not correct")
+            }
             WriteError::Duplicated => {
                 f.write_str("the pair of the left word and the right word is duplicated")
             }
             WriteError::Same => f.write_str("the left word is equal to the right word"),
+            WriteError::AttrsNotSupported => {
+                f.write_str("the binary format does not support attributes; round-trip through the text format instead")
+            }
         }
     }
 }
 
 impl Error for WriteError {}
 
+/// An error that occurs while converting between the text and binary dictionary formats.
+#[derive(Debug)]
+pub enum ConvertError {
+    Read(ReadError),
+    Write(WriteError),
+}
+
+impl From<ReadError> for ConvertError {
+    #[inline]
+    fn from(error: ReadError) -> Self {
+        ConvertError::Read(error)
+    }
+}
+
+impl From<WriteError> for ConvertError {
+    #[inline]
+    fn from(error: WriteError) -> Self {
+        ConvertError::Write(error)
+    }
+}
+
+impl Display for ConvertError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            ConvertError::Read(err) => Display::fmt(&err, f),
+            ConvertError::Write(err) => Display::fmt(&err, f),
+        }
+    }
+}
+
+impl Error for ConvertError {}
+
 use trim_in_place::TrimInPlace;
 
+/// Gzip magic bytes, used to sniff compressed dictionary files whose path
+/// doesn't end in `.gz`.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Whether `path`'s extension is (case-insensitively) `gz`.
+fn has_gzip_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// Format an entry's attributes as `" | key=value | key2=value2"` (sorted by
+/// key, for stable output), or an empty string if there are none.
+fn format_attrs(attrs: &HashMap<String, String>) -> String {
+    let mut keys: Vec<&String> = attrs.keys().collect();
+    keys.sort();
+
+    let mut formatted = String::new();
+
+    for key in keys {
+        formatted.push_str(" | ");
+        formatted.push_str(key);
+        formatted.push('=');
+        formatted.push_str(&attrs[key]);
+    }
+
+    formatted
+}
+
+/// Whether a trimmed `"| segment |"` piece of a right side unambiguously looks like a `key=value` attribute (or is blank, which is allowed between pipes).
+fn segment_is_attr_like(segment: &str) -> bool {
+    let segment = segment.trim();
+
+    if segment.is_empty() {
+        return true;
+    }
+
+    match segment.split_once('=') {
+        Some((key, _value)) => !key.trim().is_empty(),
+        None => false,
+    }
+}
+
+/// Magic bytes identifying the binary dictionary format.
+const BINARY_MAGIC: [u8; 4] = *b"WDGB";
+
+/// The current binary dictionary format version.
+const BINARY_VERSION: u8 = 1;
+
+/// Read `len` bytes at the cursor, advancing it, or fail if the buffer is too short.
+fn read_binary_bytes<'a>(bytes: &'a [u8], cursor: &mut usize, len: usize) -> Result<&'a [u8], ReadError> {
+    let slice = bytes
+        .get(*cursor..*cursor + len)
+        .ok_or_else(|| ReadError::BinaryCorrupt(String::from("unexpected end of file")))?;
+
+    *cursor += len;
+
+    Ok(slice)
+}
+
+/// Read a little-endian `u32` at the cursor, advancing it.
+fn read_binary_u32(bytes: &[u8], cursor: &mut usize) -> Result<u32, ReadError> {
+    let slice = read_binary_bytes(bytes, cursor, 4)?;
+
+    let mut array = [0u8; 4];
+    array.copy_from_slice(slice);
+
+    Ok(u32::from_le_bytes(array))
+}
+
+/// The file writer used by `write_data`: either a plain file, or a gzip
+/// encoder for paths ending in `.gz`.
+enum DictWriter {
+    Plain(File),
+    Gzip(GzEncoder<File>),
+}
+
+impl DictWriter {
+    fn new(file: File, compressed: bool) -> DictWriter {
+        if compressed {
+            DictWriter::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            DictWriter::Plain(file)
+        }
+    }
+
+    /// Flush and finalize the underlying file, writing the gzip trailer if compressed.
+    fn finish(self) -> io::Result<()> {
+        match self {
+            DictWriter::Plain(_) => Ok(()),
+            DictWriter::Gzip(encoder) => encoder.finish().map(|_| ()),
+        }
+    }
+}
+
+impl Write for DictWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            DictWriter::Plain(file) => file.write(buf),
+            DictWriter::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            DictWriter::Plain(file) => file.flush(),
+            DictWriter::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// A node of the opt-in prefix-trie index used by `find_left_prefix`.
+#[derive(Debug, Default)]
+struct TrieNode {
+    children: HashMap<char, TrieNode>,
+    indices: Vec<usize>,
+}
+
+impl TrieNode {
+    fn insert(&mut self, key: &str, index: usize) {
+        self.indices.push(index);
+
+        let mut node = self;
+
+        for c in key.chars() {
+            node = node.children.entry(c).or_default();
+            node.indices.push(index);
+        }
+    }
+
+    fn descend(&self, prefix: &str) -> Option<&TrieNode> {
+        let mut node = self;
+
+        for c in prefix.chars() {
+            node = node.children.get(&c)?;
+        }
+
+        Some(node)
+    }
+}
 
 #[derive(Debug)]
 pub struct Dictionary {
@@ -170,6 +359,10 @@ pub struct Dictionary {
     left: Vec<String>,
     /// Right data.
     right: Vec<Vec<String>>,
+    /// Named attributes (e.g. reading, part-of-speech, note) per entry.
+    attrs: Vec<HashMap<String, String>>,
+    /// The opt-in prefix-trie index, built by `build_index`.
+    index: Option<TrieNode>,
 }
 
 #[derive(Debug)]
@@ -188,6 +381,8 @@ impl Dictionary {
             path: path.into(),
             left: Vec::new(),
             right: Vec::new(),
+            attrs: Vec::new(),
+            index: None,
         }
     }
 }
@@ -197,6 +392,7 @@ impl Dictionary {
     #[inline]
     pub fn count(&self) -> usize {
         debug_assert_eq!(self.left.len(), self.right.len());
+        debug_assert_eq!(self.left.len(), self.attrs.len());
 
         self.left.len()
     }
@@ -242,6 +438,12 @@ impl Dictionary {
     pub fn get_left(&self, index: usize) -> Option<&str> {
         self.left.get(index).map(|s| s.as_str())
     }
+
+    /// Get a named attribute (e.g. reading, part-of-speech, or note) for an entry.
+    #[inline]
+    pub fn get_attr(&self, index: usize, key: &str) -> Option<&str> {
+        self.attrs.get(index)?.get(key).map(|s| s.as_str())
+    }
 }
 
 impl Dictionary {
@@ -275,6 +477,19 @@ impl Dictionary {
         None
     }
 
+    /// Find a word by exact match with a binary search, assuming `self.left` is
+    /// sorted ascending case-insensitively — true right after `read_binary`, and
+    /// right after any `write_data` or `write_binary` call, both of which sort
+    /// entries first. Prefer this over `find_left_strictly` when that holds.
+    #[inline]
+    pub fn find_left_strictly_sorted<S: AsRef<str>>(&self, s: S) -> Option<usize> {
+        let target = s.as_ref().to_uppercase();
+
+        self.left
+            .binary_search_by(|left| left.to_uppercase().cmp(&target))
+            .ok()
+    }
+
     #[inline]
     pub fn find_pairs<S: AsRef<str>>(&self, keyword: S) -> HashMap<String, Vec<String>> {
         let mut output = HashMap::new();
@@ -289,6 +504,65 @@ impl Dictionary {
         return output;
     }
 
+    /// A keyword with no exact, prefix, or substring match instead falls back to
+    /// `find_left_fuzzy` within this edit distance, so a typo still suggests something.
+    const FUZZY_FALLBACK_MAX_DISTANCE: usize = 2;
+
+    /// Find pairs for a keyword, ranked best-first by a cascade of rules: exact
+    /// match, then prefix match, then earliest substring position, then shorter
+    /// word length, then alphabetical order. Capped at 50 *after* ranking. Falls
+    /// back to `find_left_fuzzy` when nothing matches as a substring, so a typo
+    /// still suggests something.
+    #[inline]
+    pub fn find_pairs_ranked<S: AsRef<str>>(&self, keyword: S) -> Vec<(String, Vec<String>)> {
+        let keyword = keyword.as_ref();
+        let keyword_lower_case = keyword.to_lowercase();
+
+        let mut key_vector = match self.find_left_all(keyword) {
+            Some(v) => v,
+            None => return Vec::new(),
+        };
+
+        if key_vector.is_empty() {
+            return self
+                .find_left_fuzzy(keyword, Self::FUZZY_FALLBACK_MAX_DISTANCE)
+                .into_iter()
+                .map(|(index, _distance)| {
+                    let single_item = self.get_all_right_with_keys(index);
+
+                    (single_item.left.unwrap(), single_item.right)
+                })
+                .collect();
+        }
+
+        // `sort_by_cached_key` computes `ranking_key` once per index instead of
+        // once per comparison, avoiding a `to_lowercase` allocation per comparison
+        key_vector.sort_by_cached_key(|&index| Self::ranking_key(&self.left[index], &keyword_lower_case));
+
+        key_vector.truncate(50); // Only display max 50 suggestions, applied after ranking
+
+        key_vector
+            .into_iter()
+            .map(|index| {
+                let single_item = self.get_all_right_with_keys(index);
+
+                (single_item.left.unwrap(), single_item.right)
+            })
+            .collect()
+    }
+
+    /// Build a cascading ranking key for `left` against a lowercased `keyword`:
+    /// exact match, then prefix match, then earliest substring position, then
+    /// shorter word length, then alphabetical order. Ascending order is best-first.
+    fn ranking_key(left: &str, keyword_lower_case: &str) -> (u8, u8, usize, usize, String) {
+        let left_lower_case = left.to_lowercase();
+
+        let exact_rank = if left_lower_case == keyword_lower_case { 0 } else { 1 };
+        let prefix_rank = if left_lower_case.starts_with(keyword_lower_case) { 0 } else { 1 };
+        let position = left_lower_case.find(keyword_lower_case).unwrap_or(usize::MAX);
+
+        (exact_rank, prefix_rank, position, left.chars().count(), left_lower_case)
+    }
 
     /// Find a word by a keyword.
     #[inline]
@@ -338,6 +612,163 @@ impl Dictionary {
         return Some(vec);
     }
 
+    /// Find a word by a keyword, like `find_left` but without the 50-item cap,
+    /// so callers can rank the full match set before capping it themselves.
+    fn find_left_all<S: AsRef<str>>(&self, s: S) -> Option<Vec<usize>> {
+        let size = self.count();
+
+        if size == 0 {
+            return None;
+        }
+
+        let s = s.as_ref();
+
+        let s_upper_case = s.to_uppercase();
+        let s_lower_case = s.to_lowercase();
+
+        let mut vec: Vec<usize> = Vec::new();
+
+        for index in 0..size {
+            let tmp = &self.left[index];
+
+            let tmp_upper_case = tmp.to_uppercase();
+            let tmp_lower_case = tmp.to_lowercase();
+
+            if tmp_upper_case.contains(&s_upper_case) || tmp_lower_case.contains(&s_lower_case) {
+                vec.push(index);
+            }
+        }
+
+        Some(vec)
+    }
+
+    /// Find words within a bounded edit distance of a keyword, tolerating typos.
+    ///
+    /// Matches are ranked by distance (ascending), then by index, and capped at 50.
+    #[inline]
+    pub fn find_left_fuzzy<S: AsRef<str>>(&self, s: S, max_distance: usize) -> Vec<(usize, usize)> {
+        let s_lower_case = s.as_ref().to_lowercase();
+
+        let mut matches: Vec<(usize, usize)> = Vec::new();
+
+        for (index, left) in self.left.iter().enumerate() {
+            let left_lower_case = left.to_lowercase();
+
+            if let Some(distance) =
+                Self::bounded_edit_distance(&left_lower_case, &s_lower_case, max_distance)
+            {
+                matches.push((index, distance));
+            }
+        }
+
+        matches.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+        matches.truncate(50); // Only display max 50 suggestions
+
+        matches
+    }
+
+    /// Compute the Levenshtein distance between `word` and `s`, restricted to a
+    /// diagonal band of `max_distance` cells on either side, bailing out early once it's exceeded.
+    fn bounded_edit_distance(word: &str, s: &str, max_distance: usize) -> Option<usize> {
+        let s_chars: Vec<char> = s.chars().collect();
+        let word_chars: Vec<char> = word.chars().collect();
+
+        let s_len = s_chars.len();
+        let w_len = word_chars.len();
+
+        if w_len.abs_diff(s_len) > max_distance {
+            return None;
+        }
+
+        // the edit distance between two strings can never exceed the length of the
+        // longer one, so clamping here keeps `band_width` bounded regardless of how
+        // large a `max_distance` the caller passes in, without changing the result
+        let max_distance = max_distance.min(s_len.max(w_len));
+
+        // `row[off]` holds the distance for column `j = i + off - max_distance`, i.e.
+        // `off` is the offset from the diagonal; cells outside the band are `unreachable`
+        let band_width = 2 * max_distance + 1;
+        let unreachable = max_distance + 1;
+
+        let mut prev_row = vec![unreachable; band_width];
+        for j in 0..=s_len.min(max_distance) {
+            prev_row[j + max_distance] = j;
+        }
+
+        for (idx, &wc) in word_chars.iter().enumerate() {
+            let i = idx + 1;
+            let mut curr_row = vec![unreachable; band_width];
+
+            let lo = i.saturating_sub(max_distance);
+            let hi = (i + max_distance).min(s_len);
+
+            let mut row_min = if lo == 0 {
+                curr_row[max_distance - i] = i;
+                i
+            } else {
+                unreachable
+            };
+
+            for j in lo.max(1)..=hi {
+                let off = j + max_distance - i;
+                let sc = s_chars[j - 1];
+
+                let deletion = prev_row.get(off + 1).copied().unwrap_or(unreachable) + 1;
+                let insertion = if off == 0 { unreachable } else { curr_row[off - 1] + 1 };
+                let substitution = prev_row[off] + if wc != sc { 1 } else { 0 };
+
+                let value = deletion.min(insertion).min(substitution);
+                curr_row[off] = value;
+
+                if value < row_min {
+                    row_min = value;
+                }
+            }
+
+            if row_min > max_distance {
+                return None;
+            }
+
+            prev_row = curr_row;
+        }
+
+        let distance = prev_row[s_len + max_distance - w_len];
+
+        if distance <= max_distance {
+            Some(distance)
+        } else {
+            None
+        }
+    }
+
+    /// Build the in-memory prefix-trie index so `find_left_prefix` can be used. Opt-in: call it once after loading data; `rebuild_index` keeps it up to date afterwards.
+    pub fn build_index(&mut self) {
+        let mut root = TrieNode::default();
+
+        for (index, left) in self.left.iter().enumerate() {
+            root.insert(&left.to_lowercase(), index);
+        }
+
+        self.index = Some(root);
+    }
+
+    /// Rebuild the prefix-trie index, if `build_index` has already been called.
+    pub fn rebuild_index(&mut self) {
+        if self.index.is_some() {
+            self.build_index();
+        }
+    }
+
+    /// Find all indices whose left word starts with `prefix`, using the prefix-trie index built by `build_index`. Returns `None` if the index has not been built.
+    #[inline]
+    pub fn find_left_prefix<S: AsRef<str>>(&self, prefix: S) -> Option<&[usize]> {
+        let root = self.index.as_ref()?;
+
+        let prefix_lower_case = prefix.as_ref().to_lowercase();
+
+        root.descend(&prefix_lower_case).map(|node| node.indices.as_slice())
+    }
+
     /// Find a word by a keyword.
     #[inline]
     pub fn find_right_strictly<S: AsRef<str>>(
@@ -426,7 +857,19 @@ impl Dictionary {
             Err(err) => return Err(err.into()),
         };
 
-        let mut reader = BufReader::new(file);
+        let mut reader: Box<dyn BufRead> = if has_gzip_extension(&self.path) {
+            Box::new(BufReader::new(MultiGzDecoder::new(file)))
+        } else {
+            let mut peek_reader = BufReader::new(file);
+
+            let is_gzip = matches!(peek_reader.fill_buf(), Ok(buf) if buf.starts_with(&GZIP_MAGIC));
+
+            if is_gzip {
+                Box::new(BufReader::new(MultiGzDecoder::new(peek_reader)))
+            } else {
+                Box::new(peek_reader)
+            }
+        };
 
         let mut buffer = String::new();
 
@@ -447,7 +890,9 @@ impl Dictionary {
                 continue;
             }
 
-            let mut tokenizer = buffer.split('=');
+            // only the first "=" separates the left string from the rest of the line;
+            // attribute extensions ("key=value") may contain "=" of their own
+            let mut tokenizer = buffer.splitn(2, '=');
 
             let left_string = tokenizer.next().unwrap();
 
@@ -473,8 +918,8 @@ impl Dictionary {
                 });
             }
 
-            let right_string = match tokenizer.next() {
-                Some(right_string) => right_string,
+            let rest = match tokenizer.next() {
+                Some(rest) => rest,
                 None => {
                     return Err(ReadError::Broken {
                         line: line_counter,
@@ -484,7 +929,26 @@ impl Dictionary {
                 }
             };
 
-            if tokenizer.next().is_some() {
+            // the right side may carry "| key=value" attribute extensions, but a
+            // legacy entry could legitimately contain a literal "|" in its right
+            // string; only split it off when every trailing segment unambiguously
+            // looks like "key=value" (see `segment_is_attr_like`), otherwise treat
+            // the whole rest as the right string, exactly as before this format existed
+            let mut segments = rest.split('|');
+
+            let first_segment = segments.next().unwrap();
+            let trailing_segments: Vec<&str> = segments.collect();
+
+            let has_attrs = !trailing_segments.is_empty()
+                && trailing_segments.iter().all(|segment| segment_is_attr_like(segment));
+
+            let (right_string, attr_segments) = if has_attrs {
+                (first_segment, trailing_segments)
+            } else {
+                (rest, Vec::new())
+            };
+
+            if right_string.contains('=') {
                 return Err(ReadError::Broken {
                     line: line_counter,
                     left_string: String::from(left_string),
@@ -510,27 +974,158 @@ impl Dictionary {
                 right_strings.push(String::from(s));
             }
 
+            let mut attrs: HashMap<String, String> = HashMap::new();
+
+            for segment in attr_segments {
+                let segment = segment.trim();
+
+                if segment.is_empty() {
+                    continue;
+                }
+
+                // already validated as "key=value" by `segment_is_attr_like`
+                let (key, value) = segment.split_once('=').unwrap();
+
+                attrs.insert(String::from(key.trim()), String::from(value.trim()));
+            }
+
             self.left.push(String::from(left_string));
             self.right.push(right_strings);
+            self.attrs.push(attrs);
 
             line_counter += 1;
         }
 
+        self.rebuild_index();
+
+        Ok(())
+    }
+
+    /// Read the dictionary from a compact binary file written by `write_binary`, replacing any data currently held. A missing file is not an error and leaves the data unchanged. Entries loaded this way carry no attributes; round-trip through the text format to preserve them.
+    pub fn read_binary<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ReadError> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                // it is okay with a file not found error
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let mut cursor = 0usize;
+
+        if read_binary_bytes(&bytes, &mut cursor, 4)? != BINARY_MAGIC {
+            return Err(ReadError::BinaryCorrupt(String::from("bad magic bytes")));
+        }
+
+        let version = read_binary_bytes(&bytes, &mut cursor, 1)?[0];
+
+        if version != BINARY_VERSION {
+            return Err(ReadError::BinaryCorrupt(format!(
+                "unsupported binary format version {}",
+                version
+            )));
+        }
+
+        let size = read_binary_u32(&bytes, &mut cursor)? as usize;
+
+        // each entry needs at least 12 header bytes (left span + right count); reject
+        // an implausible `size` up front so a corrupt header can't trigger a huge
+        // allocation via `with_capacity` before the per-entry bounds checks below run
+        let min_remaining_bytes = size
+            .checked_mul(12)
+            .ok_or_else(|| ReadError::BinaryCorrupt(String::from("entry count overflow")))?;
+
+        if bytes.len() - cursor < min_remaining_bytes {
+            return Err(ReadError::BinaryCorrupt(String::from(
+                "entry count exceeds the remaining file size",
+            )));
+        }
+
+        let mut left_spans: Vec<(u32, u32)> = Vec::with_capacity(size);
+        let mut right_spans: Vec<Vec<(u32, u32)>> = Vec::with_capacity(size);
+
+        for _ in 0..size {
+            let left_start = read_binary_u32(&bytes, &mut cursor)?;
+            let left_end = read_binary_u32(&bytes, &mut cursor)?;
+
+            left_spans.push((left_start, left_end));
+
+            let right_count = read_binary_u32(&bytes, &mut cursor)? as usize;
+
+            // same guard as the entry count above, one level down: each right span
+            // is 8 bytes, so a corrupt `right_count` can't be trusted for
+            // `with_capacity` until it's checked against what's actually left in the file
+            let min_remaining_right_bytes = right_count
+                .checked_mul(8)
+                .ok_or_else(|| ReadError::BinaryCorrupt(String::from("right count overflow")))?;
+
+            if bytes.len() - cursor < min_remaining_right_bytes {
+                return Err(ReadError::BinaryCorrupt(String::from(
+                    "right count exceeds the remaining file size",
+                )));
+            }
+
+            let mut spans = Vec::with_capacity(right_count);
+
+            for _ in 0..right_count {
+                let start = read_binary_u32(&bytes, &mut cursor)?;
+                let end = read_binary_u32(&bytes, &mut cursor)?;
+
+                spans.push((start, end));
+            }
+
+            right_spans.push(spans);
+        }
+
+        let buffer = &bytes[cursor..];
+
+        let span_str = |span: (u32, u32)| -> Result<String, ReadError> {
+            let slice = buffer
+                .get(span.0 as usize..span.1 as usize)
+                .ok_or_else(|| ReadError::BinaryCorrupt(String::from("span out of bounds")))?;
+
+            std::str::from_utf8(slice)
+                .map(String::from)
+                .map_err(|_| ReadError::BinaryCorrupt(String::from("invalid UTF-8 in span")))
+        };
+
+        let mut left: Vec<String> = Vec::with_capacity(size);
+        let mut right: Vec<Vec<String>> = Vec::with_capacity(size);
+
+        for (index, spans) in right_spans.into_iter().enumerate() {
+            left.push(span_str(left_spans[index])?);
+
+            let mut right_words = Vec::with_capacity(spans.len());
+
+            for span in spans {
+                right_words.push(span_str(span)?);
+            }
+
+            right.push(right_words);
+        }
+
+        self.left = left;
+        self.right = right;
+        self.attrs = vec![HashMap::new(); size];
+
+        self.rebuild_index();
+
         Ok(())
     }
 }
 
 impl Dictionary {
-    /// Write this dictionary to its dictionary file.
-    pub fn write_data(&mut self) -> Result<(), WriteError> {
-        let mut file = File::create(&self.path)?;
-
+    /// Sort entries ascending by the left word (case-insensitively), in place, via exchange sort, then rebuild the index.
+    fn sort_entries(&mut self) {
         let size = self.count();
 
         if size > 0 {
             let size_dec = size - 1;
 
-            // When doing exchange sort, it also writes data to file.
             for i in 0..size_dec {
                 let mut left = self.left[i].to_uppercase();
 
@@ -542,16 +1137,139 @@ impl Dictionary {
 
                         self.right.swap(i, j);
 
+                        self.attrs.swap(i, j);
+
                         left = left_2;
                     }
                 }
+            }
+        }
+
+        self.rebuild_index();
+    }
+
+    /// Write this dictionary to its dictionary file.
+    pub fn write_data(&mut self) -> Result<(), WriteError> {
+        let file = File::create(&self.path)?;
+
+        let mut writer = DictWriter::new(file, has_gzip_extension(&self.path));
+
+        self.sort_entries();
+
+        let size = self.count();
+
+        if size > 0 {
+            let size_dec = size - 1;
+
+            for i in 0..size_dec {
+                writeln!(
+                    writer,
+                    "{} = {}{}",
+                    self.left[i],
+                    self.right[i].join(" --> "),
+                    format_attrs(&self.attrs[i]),
+                )?;
+            }
+
+            // always newline-terminate, even the last entry: a trailing newline is what
+            // lets `MultiGzDecoder` read a concatenated append (`cat a.gz b.gz`) correctly,
+            // since otherwise `a`'s last line glues onto `b`'s first line once decompressed
+            writeln!(
+                writer,
+                "{} = {}{}",
+                self.left[size_dec],
+                self.right[size_dec].join(" --> "),
+                format_attrs(&self.attrs[size_dec]),
+            )?;
+        }
 
-                writeln!(file, "{} = {}", self.left[i], self.right[i].join(" --> "))?;
+        writer.finish()?;
+
+        Ok(())
+    }
+
+    /// Write this dictionary to a compact binary file at `path`, sorted first so `find_left_strictly_sorted` can binary-search the result after `read_binary` loads it back. Fails with `WriteError::AttrsNotSupported` if any entry has attributes, which the binary format does not carry.
+    pub fn write_binary<P: AsRef<Path>>(&mut self, path: P) -> Result<(), WriteError> {
+        if self.attrs.iter().any(|attrs| !attrs.is_empty()) {
+            return Err(WriteError::AttrsNotSupported);
+        }
+
+        self.sort_entries();
+
+        let size = self.count();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut left_spans: Vec<(u32, u32)> = Vec::with_capacity(size);
+        let mut right_spans: Vec<Vec<(u32, u32)>> = Vec::with_capacity(size);
+
+        for i in 0..size {
+            let start = buffer.len() as u32;
+            buffer.extend_from_slice(self.left[i].as_bytes());
+            let end = buffer.len() as u32;
+
+            left_spans.push((start, end));
+
+            let mut spans = Vec::with_capacity(self.right[i].len());
+
+            for right in &self.right[i] {
+                let start = buffer.len() as u32;
+                buffer.extend_from_slice(right.as_bytes());
+                let end = buffer.len() as u32;
+
+                spans.push((start, end));
             }
 
-            write!(file, "{} = {}", self.left[size_dec], self.right[size_dec].join(" --> "))?;
+            right_spans.push(spans);
         }
 
+        let mut file = File::create(path)?;
+
+        file.write_all(&BINARY_MAGIC)?;
+        file.write_all(&[BINARY_VERSION])?;
+        file.write_all(&(size as u32).to_le_bytes())?;
+
+        for i in 0..size {
+            let (left_start, left_end) = left_spans[i];
+
+            file.write_all(&left_start.to_le_bytes())?;
+            file.write_all(&left_end.to_le_bytes())?;
+
+            file.write_all(&(right_spans[i].len() as u32).to_le_bytes())?;
+
+            for (start, end) in &right_spans[i] {
+                file.write_all(&start.to_le_bytes())?;
+                file.write_all(&end.to_le_bytes())?;
+            }
+        }
+
+        file.write_all(&buffer)?;
+
+        Ok(())
+    }
+
+    /// Convert a human-editable text dictionary file into the compact binary format.
+    pub fn convert_text_to_binary<P1: Into<PathBuf>, P2: AsRef<Path>>(
+        text_path: P1,
+        binary_path: P2,
+    ) -> Result<(), ConvertError> {
+        let mut dictionary = Dictionary::new(text_path);
+
+        dictionary.read_data()?;
+        dictionary.write_binary(binary_path)?;
+
+        Ok(())
+    }
+
+    /// Convert a compact binary dictionary file back into the human-editable text format.
+    pub fn convert_binary_to_text<P1: AsRef<Path>, P2: Into<PathBuf>>(
+        binary_path: P1,
+        text_path: P2,
+    ) -> Result<(), ConvertError> {
+        let mut dictionary = Dictionary::new(text_path);
+
+        dictionary.read_binary(binary_path)?;
+        dictionary.write_data()?;
+
         Ok(())
     }
 
@@ -561,7 +1279,9 @@ impl Dictionary {
         if index < self.count() {
             self.left.remove(index);
             self.right.remove(index);
+            self.attrs.remove(index);
 
+            // `write_data` already rebuilds the index via `sort_entries`
             self.write_data()?;
 
             Ok(true)
@@ -579,9 +1299,9 @@ impl Dictionary {
         let left = left.as_ref().trim();
         let right = right.as_ref().trim();
 
-        if left.contains("-->") || left.contains('=') {
+        if left.contains("-->") || left.contains('=') || left.contains('|') || left.contains(['\n', '\r']) {
             Err(WriteError::BadLeftString)
-        } else if right.contains("-->") || right.contains('=') {
+        } else if right.contains("-->") || right.contains('=') || right.contains('|') || right.contains(['\n', '\r']) {
             Err(WriteError::BadRightString)
         } else if left == right {
             Err(WriteError::Same)
@@ -591,6 +1311,7 @@ impl Dictionary {
             } else {
                 self.right.get_mut(index).unwrap().push(String::from(right));
 
+                // `write_data` already rebuilds the index via `sort_entries`
                 self.write_data()?;
 
                 Ok(false)
@@ -598,10 +1319,416 @@ impl Dictionary {
         } else {
             self.left.push(String::from(left));
             self.right.push(vec![String::from(right)]);
+            self.attrs.push(HashMap::new());
 
+            // `write_data` already rebuilds the index via `sort_entries`
             self.write_data()?;
 
             Ok(true)
         }
     }
+
+    /// Add or edit a word along with named attributes (e.g. reading, part-of-speech,
+    /// or note). If the left word exists, then update it, merging the given
+    /// attributes into the existing ones, and return `Ok(false)`.
+    pub fn add_edit_with_attrs<L: AsRef<str>, R: AsRef<str>>(
+        &mut self,
+        left: L,
+        right: R,
+        attrs: HashMap<String, String>,
+    ) -> Result<bool, WriteError> {
+        let left = left.as_ref().trim();
+        let right = right.as_ref().trim();
+
+        if left.contains("-->") || left.contains('=') || left.contains('|') || left.contains(['\n', '\r']) {
+            Err(WriteError::BadLeftString)
+        } else if right.contains("-->") || right.contains('=') || right.contains('|') || right.contains(['\n', '\r']) {
+            Err(WriteError::BadRightString)
+        } else if attrs.iter().any(|(key, value)| {
+            key.contains(['=', '|', '\n', '\r']) || value.contains(['=', '|', '\n', '\r'])
+        }) {
+            Err(WriteError::BadAttrString)
+        } else if left == right {
+            Err(WriteError::Same)
+        } else if let Some(index) = self.find_left_strictly(left, 0) {
+            let is_same_right = self.get_right(index).unwrap() == right;
+
+            if is_same_right && attrs.is_empty() {
+                Err(WriteError::Duplicated)
+            } else {
+                if !is_same_right {
+                    self.right.get_mut(index).unwrap().push(String::from(right));
+                }
+
+                self.attrs.get_mut(index).unwrap().extend(attrs);
+
+                // `write_data` already rebuilds the index via `sort_entries`
+                self.write_data()?;
+
+                Ok(false)
+            }
+        } else {
+            self.left.push(String::from(left));
+            self.right.push(vec![String::from(right)]);
+            self.attrs.push(attrs);
+
+            // `write_data` already rebuilds the index via `sort_entries`
+            self.write_data()?;
+
+            Ok(true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A dictionary path under the system temp dir, unique to this test run,
+    /// removed again when the guard drops.
+    struct TempDictPath(PathBuf);
+
+    impl TempDictPath {
+        fn new(name: &str) -> TempDictPath {
+            TempDictPath::new_ext(name, "txt")
+        }
+
+        fn new_ext(name: &str, ext: &str) -> TempDictPath {
+            let path = std::env::temp_dir().join(format!(
+                "word_dictionary_test_{}_{}.{}",
+                name,
+                std::process::id(),
+                ext
+            ));
+
+            TempDictPath(path)
+        }
+    }
+
+    impl Drop for TempDictPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn find_pairs_ranked_orders_by_the_cascade_of_rules() {
+        let path = TempDictPath::new("ranked_cascade");
+        let mut dictionary = Dictionary::new(path.0.clone());
+
+        // deliberately inserted out of the expected result order
+        dictionary.add_edit("concatenate", "A").unwrap(); // substring at position 3, longest
+        dictionary.add_edit("muscat", "B").unwrap(); // substring at position 3, shorter
+        dictionary.add_edit("catalog", "C").unwrap(); // prefix match
+        dictionary.add_edit("scat", "D").unwrap(); // substring at position 1
+        dictionary.add_edit("cat", "E").unwrap(); // exact match
+
+        let ranked = dictionary.find_pairs_ranked("cat");
+        let order: Vec<&str> = ranked.iter().map(|(left, _)| left.as_str()).collect();
+
+        // exact match first, then the prefix match, then the remaining substring
+        // matches ordered by earliest match position and then by shorter length
+        assert_eq!(
+            vec!["cat", "catalog", "scat", "muscat", "concatenate"],
+            order
+        );
+    }
+
+    #[test]
+    fn find_pairs_ranked_still_matches_substrings_after_build_index() {
+        let path = TempDictPath::new("ranked_unaffected_by_index");
+        let mut dictionary = Dictionary::new(path.0.clone());
+
+        dictionary.add_edit("cat", "E").unwrap();
+        dictionary.add_edit("catalog", "C").unwrap();
+        dictionary.add_edit("muscat", "B").unwrap(); // substring match, not a prefix match
+
+        dictionary.build_index();
+
+        // building the prefix-trie index is an orthogonal optimization for
+        // `find_left_prefix`; it must not narrow `find_pairs_ranked`'s match set
+        let ranked = dictionary.find_pairs_ranked("cat");
+        let order: Vec<&str> = ranked.iter().map(|(left, _)| left.as_str()).collect();
+
+        assert_eq!(vec!["cat", "catalog", "muscat"], order);
+    }
+
+    #[test]
+    fn find_pairs_ranked_falls_back_to_fuzzy_matches_when_nothing_matches_as_a_substring() {
+        let path = TempDictPath::new("ranked_fuzzy_fallback");
+        let mut dictionary = Dictionary::new(path.0.clone());
+
+        dictionary.add_edit("hello", "你好").unwrap();
+
+        let ranked = dictionary.find_pairs_ranked("helo");
+        let order: Vec<&str> = ranked.iter().map(|(left, _)| left.as_str()).collect();
+
+        assert_eq!(vec!["hello"], order);
+    }
+
+    #[test]
+    fn find_left_fuzzy_tolerates_a_single_typo() {
+        let path = TempDictPath::new("fuzzy_typo");
+        let mut dictionary = Dictionary::new(path.0.clone());
+
+        dictionary.add_edit("hello", "你好").unwrap();
+        dictionary.add_edit("world", "世界").unwrap();
+
+        let matches = dictionary.find_left_fuzzy("helo", 1);
+        let hello_index = dictionary.find_left_strictly("hello", 0).unwrap();
+
+        assert_eq!(vec![(hello_index, 1)], matches);
+    }
+
+    #[test]
+    fn find_left_fuzzy_does_not_panic_on_a_huge_max_distance() {
+        let path = TempDictPath::new("fuzzy_overflow");
+        let mut dictionary = Dictionary::new(path.0.clone());
+
+        dictionary.add_edit("hello", "你好").unwrap();
+
+        // `usize::MAX / 2 + 1` used to overflow `2 * max_distance + 1` in
+        // `bounded_edit_distance`; it must now just return a match.
+        let matches = dictionary.find_left_fuzzy("hello", usize::MAX / 2 + 1);
+
+        assert_eq!(vec![(0, 0)], matches);
+    }
+
+    #[test]
+    fn find_left_prefix_returns_none_until_the_index_is_built() {
+        let path = TempDictPath::new("prefix_not_built");
+        let mut dictionary = Dictionary::new(path.0.clone());
+
+        dictionary.add_edit("Alduin", "阿爾杜因").unwrap();
+
+        assert!(dictionary.find_left_prefix("Ald").is_none());
+
+        dictionary.build_index();
+
+        assert_eq!(
+            vec![dictionary.find_left_strictly("Alduin", 0).unwrap()],
+            dictionary.find_left_prefix("Ald").unwrap()
+        );
+    }
+
+    #[test]
+    fn find_left_prefix_stays_correct_after_add_edit() {
+        let path = TempDictPath::new("prefix_after_add");
+        let mut dictionary = Dictionary::new(path.0.clone());
+
+        dictionary.add_edit("Aldun", "奧爾敦").unwrap();
+        dictionary.build_index();
+        dictionary.add_edit("Alduin", "阿爾杜因").unwrap();
+
+        let aldun_index = dictionary.find_left_strictly("Aldun", 0).unwrap();
+        let alduin_index = dictionary.find_left_strictly("Alduin", 0).unwrap();
+
+        let mut found: Vec<usize> = dictionary.find_left_prefix("Ald").unwrap().to_vec();
+        found.sort();
+
+        let mut expected = vec![aldun_index, alduin_index];
+        expected.sort();
+
+        assert_eq!(expected, found);
+    }
+
+    #[test]
+    fn add_edit_with_attrs_round_trips_through_read_data() {
+        let path = TempDictPath::new("attrs_round_trip");
+
+        {
+            let mut dictionary = Dictionary::new(path.0.clone());
+
+            let mut attrs = HashMap::new();
+            attrs.insert(String::from("reading"), String::from("foo"));
+            attrs.insert(String::from("note"), String::from("a note"));
+
+            dictionary.add_edit_with_attrs("foo", "bar", attrs).unwrap();
+        }
+
+        let mut dictionary = Dictionary::new(path.0.clone());
+        dictionary.read_data().unwrap();
+
+        let index = dictionary.find_left_strictly("foo", 0).unwrap();
+
+        assert_eq!(Some("foo"), dictionary.get_attr(index, "reading"));
+        assert_eq!(Some("a note"), dictionary.get_attr(index, "note"));
+    }
+
+    #[test]
+    fn add_edit_with_attrs_rejects_a_key_containing_the_delimiters() {
+        let path = TempDictPath::new("attrs_bad_key");
+        let mut dictionary = Dictionary::new(path.0.clone());
+
+        let mut attrs = HashMap::new();
+        attrs.insert(String::from("a=b"), String::from("v"));
+
+        assert!(matches!(
+            dictionary.add_edit_with_attrs("foo", "bar", attrs),
+            Err(WriteError::BadAttrString)
+        ));
+        assert_eq!(0, dictionary.count());
+    }
+
+    #[test]
+    fn add_edit_with_attrs_rejects_a_value_containing_a_pipe() {
+        let path = TempDictPath::new("attrs_bad_value");
+        let mut dictionary = Dictionary::new(path.0.clone());
+
+        let mut attrs = HashMap::new();
+        attrs.insert(String::from("note"), String::from("a|b"));
+
+        assert!(matches!(
+            dictionary.add_edit_with_attrs("foo", "bar", attrs),
+            Err(WriteError::BadAttrString)
+        ));
+        assert_eq!(0, dictionary.count());
+    }
+
+    #[test]
+    fn add_edit_with_attrs_rejects_a_value_containing_a_newline() {
+        let path = TempDictPath::new("attrs_bad_newline");
+        let mut dictionary = Dictionary::new(path.0.clone());
+
+        let mut attrs = HashMap::new();
+        attrs.insert(String::from("note"), String::from("a\nbroken = entry"));
+
+        assert!(matches!(
+            dictionary.add_edit_with_attrs("foo", "bar", attrs),
+            Err(WriteError::BadAttrString)
+        ));
+        assert_eq!(0, dictionary.count());
+    }
+
+    #[test]
+    fn write_binary_round_trips_through_read_binary() {
+        let text_path = TempDictPath::new("binary_round_trip_text");
+        let binary_path = TempDictPath::new("binary_round_trip_bin");
+
+        let mut dictionary = Dictionary::new(text_path.0.clone());
+
+        dictionary.add_edit("Althasol", "阿爾瑟索").unwrap();
+        dictionary.add_edit("Aldun", "奧爾敦").unwrap();
+        dictionary.add_edit("Alduin", "阿爾杜因").unwrap();
+        dictionary.add_edit("Alduin", "奥杜因").unwrap();
+
+        dictionary.write_binary(&binary_path.0).unwrap();
+
+        let mut loaded = Dictionary::new(text_path.0.clone());
+        loaded.read_binary(&binary_path.0).unwrap();
+
+        assert_eq!(3, loaded.count());
+        assert_eq!(
+            "阿爾瑟索",
+            loaded
+                .get_right(loaded.find_left_strictly("Althasol", 0).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            "奥杜因",
+            loaded
+                .get_right(loaded.find_left_strictly("Alduin", 0).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn write_binary_rejects_entries_with_attrs_instead_of_dropping_them() {
+        let text_path = TempDictPath::new("binary_attrs_text");
+        let binary_path = TempDictPath::new("binary_attrs_bin");
+
+        let mut dictionary = Dictionary::new(text_path.0.clone());
+
+        let mut attrs = HashMap::new();
+        attrs.insert(String::from("reading"), String::from("foo"));
+
+        dictionary.add_edit_with_attrs("foo", "bar", attrs).unwrap();
+
+        assert!(matches!(
+            dictionary.write_binary(&binary_path.0),
+            Err(WriteError::AttrsNotSupported)
+        ));
+    }
+
+    #[test]
+    fn find_left_prefix_stays_correct_after_write_data_sorts_entries() {
+        let path = TempDictPath::new("index_after_sort");
+        let mut dictionary = Dictionary::new(path.0.clone());
+
+        dictionary.build_index();
+
+        // inserted out of sorted order, so `sort_entries` (called by `write_data`)
+        // actually has to move entries around
+        dictionary.add_edit("zebra", "Z").unwrap();
+        dictionary.add_edit("apple", "A").unwrap();
+
+        // `write_data` runs on every `add_edit`, so the index was already rebuilt
+        // above; write again directly to pin the behavior this test targets
+        dictionary.write_data().unwrap();
+
+        let apple_index = dictionary.find_left_strictly("apple", 0).unwrap();
+        let zebra_index = dictionary.find_left_strictly("zebra", 0).unwrap();
+
+        assert_eq!(Some(&[apple_index][..]), dictionary.find_left_prefix("app"));
+        assert_eq!(Some(&[zebra_index][..]), dictionary.find_left_prefix("zeb"));
+    }
+
+    #[test]
+    fn gzip_dictionary_round_trips_through_read_data() {
+        let path = TempDictPath::new_ext("gzip_round_trip", "gz");
+
+        {
+            let mut dictionary = Dictionary::new(path.0.clone());
+            dictionary.add_edit("hello", "你好").unwrap();
+        }
+
+        let mut loaded = Dictionary::new(path.0.clone());
+        loaded.read_data().unwrap();
+
+        assert_eq!(
+            "你好",
+            loaded
+                .get_right(loaded.find_left_strictly("hello", 0).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn gzip_read_data_handles_a_concatenated_multi_member_file() {
+        let path_a = TempDictPath::new_ext("gzip_member_a", "gz");
+        let path_b = TempDictPath::new_ext("gzip_member_b", "gz");
+        let combined = TempDictPath::new_ext("gzip_member_combined", "gz");
+
+        {
+            let mut dictionary = Dictionary::new(path_a.0.clone());
+            dictionary.add_edit("alpha", "1").unwrap();
+        }
+        {
+            let mut dictionary = Dictionary::new(path_b.0.clone());
+            dictionary.add_edit("beta", "2").unwrap();
+        }
+
+        // the exact append scenario `MultiGzDecoder` exists to support: two
+        // independently gzip-compressed dictionaries concatenated byte-for-byte
+        let mut combined_bytes = std::fs::read(&path_a.0).unwrap();
+        combined_bytes.extend(std::fs::read(&path_b.0).unwrap());
+        std::fs::write(&combined.0, combined_bytes).unwrap();
+
+        let mut loaded = Dictionary::new(combined.0.clone());
+        loaded.read_data().unwrap();
+
+        assert_eq!(2, loaded.count());
+        assert_eq!(
+            "1",
+            loaded
+                .get_right(loaded.find_left_strictly("alpha", 0).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            "2",
+            loaded
+                .get_right(loaded.find_left_strictly("beta", 0).unwrap())
+                .unwrap()
+        );
+    }
 }
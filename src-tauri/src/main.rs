@@ -4,7 +4,6 @@ mod dictionary_module;
 use std::process::{Command};
 use std::path::PathBuf;
 
-use std::collections::HashMap;
 use tauri::Error;
 use dictionary_module::Dictionary;
 
@@ -25,13 +24,13 @@ fn print_cmd_output(name: String, cwd: String) -> CommandOutput {
 
 
 #[tauri::command]
-fn get_suggestions(name: &str) -> Result<HashMap<String, Vec<String>>, Error> {
-    
+fn get_suggestions(name: &str) -> Result<Vec<(String, Vec<String>)>, Error> {
+
     let mut dictionary = Dictionary::new("dictionary.db");
 
     dictionary.read_data().unwrap();
 
-    let output = dictionary.find_pairs(name);
+    let output = dictionary.find_pairs_ranked(name);
 
     return Ok(output);
 }